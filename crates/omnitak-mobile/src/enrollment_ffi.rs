@@ -6,6 +6,10 @@ use std::ffi::CStr;
 use std::os::raw::{c_char, c_int};
 use std::ptr;
 use std::sync::Arc;
+use openssl::pkcs12::Pkcs12;
+use openssl::pkey::PKey;
+use openssl::stack::Stack;
+use openssl::x509::X509;
 use parking_lot::Mutex;
 
 use omnitak_cert::enrollment::{EnrollmentClient, EnrollmentRequest};
@@ -253,3 +257,201 @@ pub extern "C" fn omnitak_enrollment_clear_result() {
     let mut result = LAST_ENROLLMENT_RESULT.lock();
     *result = None;
 }
+
+/// Serialize a stored `EnrollmentResult` into a PKCS#12 blob
+///
+/// This is the format TAK mobile clients expect to import as a data
+/// package identity, bundling the client cert, private key, and CA chain
+/// that `omnitak_enrollment_get_result` otherwise returns as separate PEMs.
+fn build_p12(result: &EnrollmentResult, password: &str) -> Result<Vec<u8>, String> {
+    let cert = X509::from_pem(result.cert_pem.as_bytes())
+        .map_err(|e| format!("invalid enrollment cert PEM: {}", e))?;
+    let pkey = PKey::private_key_from_pem(result.key_pem.as_bytes())
+        .map_err(|e| format!("invalid enrollment key PEM: {}", e))?;
+
+    let mut builder = Pkcs12::builder();
+    if let Some(ca_pem) = &result.ca_pem {
+        // `ca_pem` may concatenate an intermediate + root (or longer) chain;
+        // `X509::from_pem` only decodes the first cert, which would silently
+        // truncate the trust chain, so parse every cert in the PEM instead.
+        let ca_certs = X509::stack_from_pem(ca_pem.as_bytes())
+            .map_err(|e| format!("invalid enrollment CA PEM: {}", e))?;
+        let mut chain = Stack::new().map_err(|e| format!("failed to build CA chain: {}", e))?;
+        for ca_cert in ca_certs {
+            chain
+                .push(ca_cert)
+                .map_err(|e| format!("failed to append CA cert: {}", e))?;
+        }
+        builder.ca(chain);
+    }
+
+    let pkcs12 = builder
+        .build2(password, "omnitak", &pkey, &cert)
+        .map_err(|e| format!("failed to build PKCS#12 bundle: {}", e))?;
+    pkcs12
+        .to_der()
+        .map_err(|e| format!("failed to serialize PKCS#12 bundle: {}", e))
+}
+
+/// Get the result of the last enrollment operation as a PKCS#12 bundle
+///
+/// Unlike `omnitak_enrollment_get_result`, which returns separate PEM
+/// strings, this bundles the cert, key, and CA chain into a single
+/// password-protected `.p12` blob — the format mobile TAK clients expect
+/// to import.
+///
+/// # Parameters
+/// - `out_buf`: Buffer to receive the PKCS#12 DER bytes (or null to query the required size)
+/// - `out_len`: In: capacity of `out_buf`. Out: number of bytes written (or required, if `out_buf` was too small)
+/// - `password`: Null-terminated C string used to encrypt the bundle
+///
+/// # Returns
+/// 1 if enrollment succeeded and the bundle was written
+/// -1 if no enrollment result is available
+/// -2 if `password` is invalid or building the bundle failed
+/// -3 if `out_buf` is too small (`out_len` is set to the required size)
+///
+/// # Safety
+/// `out_buf` must be valid for `*out_len` bytes, or null; `out_len` and
+/// `password` must be valid pointers
+#[no_mangle]
+pub unsafe extern "C" fn omnitak_enrollment_get_result_p12(
+    out_buf: *mut u8,
+    out_len: *mut usize,
+    password: *const c_char,
+) -> c_int {
+    if out_len.is_null() || password.is_null() {
+        eprintln!("omnitak_enrollment_get_result_p12: null parameter");
+        return -2;
+    }
+
+    let password_str = match CStr::from_ptr(password).to_str() {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("omnitak_enrollment_get_result_p12: invalid password: {}", e);
+            return -2;
+        }
+    };
+
+    let result_lock = LAST_ENROLLMENT_RESULT.lock();
+    let result = match result_lock.as_ref() {
+        Some(r) => r,
+        None => return -1,
+    };
+
+    let der = match build_p12(result, password_str) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            eprintln!("omnitak_enrollment_get_result_p12: {}", e);
+            return -2;
+        }
+    };
+
+    let capacity = *out_len;
+    *out_len = der.len();
+    if out_buf.is_null() || capacity < der.len() {
+        return -3;
+    }
+
+    ptr::copy_nonoverlapping(der.as_ptr(), out_buf, der.len());
+    1
+}
+
+/// Reload the server's TLS certificate/key from disk immediately
+///
+/// Call this right after a successful `omnitak_enroll` so a freshly
+/// renewed certificate takes effect without restarting the server,
+/// instead of waiting for the filesystem watcher to notice the change.
+///
+/// Delegates to whichever `ReloadableTlsAcceptor` a running
+/// `omnitak_server::TakServer` registered via
+/// `omnitak_server::tls::register_reloadable_acceptor`.
+///
+/// # Returns
+/// 0 on success, -1 if no TAK server has registered a TLS acceptor yet,
+/// -2 if reloading failed
+#[no_mangle]
+pub extern "C" fn omnitak_tls_reload() -> c_int {
+    if !omnitak_server::tls::has_registered_tls_acceptor() {
+        eprintln!("omnitak_tls_reload: no TLS acceptor registered");
+        return -1;
+    }
+
+    match omnitak_server::tls::reload_registered_tls_acceptor() {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("omnitak_tls_reload: reload failed: {}", e);
+            -2
+        }
+    }
+}
+
+#[cfg(test)]
+mod p12_tests {
+    use super::*;
+    use std::ffi::CString;
+
+    const P12_PASSWORD: &str = "test-password";
+
+    fn self_signed_pem(cn: &str) -> (String, String) {
+        let cert = rcgen::generate_simple_self_signed(vec![cn.into()]).unwrap();
+        (cert.serialize_pem().unwrap(), cert.serialize_private_key_pem())
+    }
+
+    fn enrollment_result(ca_pem: Option<String>) -> EnrollmentResult {
+        let (cert_pem, key_pem) = self_signed_pem("omnitak-enrollment-test");
+        EnrollmentResult {
+            cert_pem,
+            key_pem,
+            ca_pem,
+            server_host: "tak.example.test".into(),
+            server_port: 8089,
+        }
+    }
+
+    #[test]
+    fn build_p12_round_trips_without_a_ca_chain() {
+        let result = enrollment_result(None);
+        let der = build_p12(&result, P12_PASSWORD).expect("build p12 without ca chain");
+
+        let pkcs12 = Pkcs12::from_der(&der).expect("parse generated pkcs12");
+        let parsed = pkcs12.parse2(P12_PASSWORD).expect("unlock generated pkcs12");
+        assert!(parsed.cert.is_some());
+        assert!(parsed.pkey.is_some());
+    }
+
+    #[test]
+    fn build_p12_preserves_every_cert_in_a_multi_cert_ca_chain() {
+        // A real TAK CA chain is an intermediate concatenated with a root;
+        // `build_p12` must keep both, not just the first cert in `ca_pem`.
+        let (intermediate_pem, _) = self_signed_pem("omnitak-intermediate-test");
+        let (root_pem, _) = self_signed_pem("omnitak-root-test");
+        let ca_pem = format!("{}\n{}", intermediate_pem, root_pem);
+
+        let result = enrollment_result(Some(ca_pem));
+        let der = build_p12(&result, P12_PASSWORD).expect("build p12 with multi-cert ca chain");
+
+        let pkcs12 = Pkcs12::from_der(&der).expect("parse generated pkcs12");
+        let parsed = pkcs12.parse2(P12_PASSWORD).expect("unlock generated pkcs12");
+        let ca_chain = parsed.ca.expect("ca chain should round-trip");
+        assert_eq!(ca_chain.len(), 2, "both CA certs should survive re-export, not just the first");
+    }
+
+    #[test]
+    fn omnitak_enrollment_get_result_p12_writes_a_loadable_bundle() {
+        *LAST_ENROLLMENT_RESULT.lock() = Some(enrollment_result(None));
+
+        let password = CString::new(P12_PASSWORD).unwrap();
+        let mut buf = vec![0u8; 8192];
+        let mut len = buf.len();
+        let rc = unsafe {
+            omnitak_enrollment_get_result_p12(buf.as_mut_ptr(), &mut len, password.as_ptr())
+        };
+        assert_eq!(rc, 1);
+
+        let pkcs12 = Pkcs12::from_der(&buf[..len]).expect("parse ffi-exported pkcs12");
+        pkcs12.parse2(P12_PASSWORD).expect("unlock ffi-exported pkcs12");
+
+        omnitak_enrollment_clear_result();
+    }
+}
@@ -9,7 +9,7 @@
 //!   # Then run the TLS server:
 //!   cargo run --example tls_server
 
-use omnitak_server::{ServerConfig, TakServer, config::TlsConfig};
+use omnitak_server::{ServerConfig, TakServer, config::{KeepAliveConfig, TlsConfig}};
 use std::path::PathBuf;
 use tracing::info;
 use tracing_subscriber::{fmt, prelude::*, EnvFilter};
@@ -35,6 +35,8 @@ async fn main() -> anyhow::Result<()> {
         key_path: certs_dir.join("server-key.pem"),
         ca_path: Some(certs_dir.join("ca-cert.pem")),
         require_client_cert: true,
+        // Set this if server-key.pem is an `ENCRYPTED PRIVATE KEY` block.
+        key_password: None,
     };
 
     // Create server configuration
@@ -42,15 +44,18 @@ async fn main() -> anyhow::Result<()> {
         bind_address: "0.0.0.0".parse().unwrap(),
         tcp_port: 0, // Disable TCP
         tls_port: 8090,
+        quic_port: 8091,
         tls: Some(tls_config),
         debug: true,
         max_clients: 1000,
         client_timeout_secs: 300,
         marti_port: 0,
         data_package_dir: None,
+        keepalive: KeepAliveConfig::default(),
     };
 
     info!("Starting TLS server on port {}", config.tls_port);
+    info!("Starting QUIC server on port {}", config.quic_port);
     info!("Client certificates REQUIRED");
     info!("Debug logging enabled - all CoT messages will be logged");
     info!("Press Ctrl+C to stop");
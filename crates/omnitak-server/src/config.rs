@@ -0,0 +1,55 @@
+//! Server configuration types
+
+use std::net::IpAddr;
+use std::path::PathBuf;
+
+/// TLS identity and client-verification settings
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+    pub ca_path: Option<PathBuf>,
+    pub require_client_cert: bool,
+    /// Password for `key_path`, if it holds an encrypted PKCS#8 key
+    pub key_password: Option<String>,
+}
+
+/// Idle keep-alive settings shared by the TCP, TLS, and QUIC transports
+#[derive(Debug, Clone)]
+pub struct KeepAliveConfig {
+    /// Whether idle connections are pinged before being disconnected
+    pub enabled: bool,
+    /// How long a connection may sit silent before a ping is sent
+    pub keepalive_interval_secs: u64,
+    /// Overrides the default CoT ping XML; `None` uses `keepalive::cot_ping_payload`
+    pub ping_payload: Option<Vec<u8>>,
+}
+
+impl Default for KeepAliveConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            keepalive_interval_secs: 15,
+            ping_payload: None,
+        }
+    }
+}
+
+/// TAK server configuration
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    pub bind_address: IpAddr,
+    /// Plain TCP port; 0 disables the transport
+    pub tcp_port: u16,
+    /// TLS-over-TCP port; 0 disables the transport
+    pub tls_port: u16,
+    /// QUIC port; 0 disables the transport. Requires `tls` to be set
+    pub quic_port: u16,
+    pub tls: Option<TlsConfig>,
+    pub debug: bool,
+    pub max_clients: usize,
+    pub client_timeout_secs: u64,
+    pub marti_port: u16,
+    pub data_package_dir: Option<PathBuf>,
+    pub keepalive: KeepAliveConfig,
+}
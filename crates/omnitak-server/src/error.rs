@@ -0,0 +1,48 @@
+//! Error types for the TAK server
+
+use std::fmt;
+
+/// Result alias used throughout `omnitak-server`
+pub type Result<T> = std::result::Result<T, ServerError>;
+
+/// Errors produced while configuring or running a [`crate::server::TakServer`]
+#[derive(Debug)]
+pub enum ServerError {
+    /// A certificate or private key file was missing, malformed, or couldn't be decrypted
+    Certificate(String),
+    /// `rustls`/`quinn` rejected a TLS/QUIC config or handshake
+    Tls(String),
+    /// The supplied `ServerConfig` was internally inconsistent
+    Config(String),
+    /// A connection was torn down (e.g. an idle keep-alive timeout)
+    Connection(String),
+    /// Underlying I/O failure
+    Io(std::io::Error),
+}
+
+impl fmt::Display for ServerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServerError::Certificate(msg) => write!(f, "certificate error: {}", msg),
+            ServerError::Tls(msg) => write!(f, "TLS error: {}", msg),
+            ServerError::Config(msg) => write!(f, "configuration error: {}", msg),
+            ServerError::Connection(msg) => write!(f, "connection error: {}", msg),
+            ServerError::Io(e) => write!(f, "I/O error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ServerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ServerError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ServerError {
+    fn from(e: std::io::Error) -> Self {
+        ServerError::Io(e)
+    }
+}
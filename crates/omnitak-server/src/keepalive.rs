@@ -0,0 +1,149 @@
+//! Idle keep-alive and inactivity disconnect for connected clients
+//!
+//! `client_timeout_secs` on [`crate::config::ServerConfig`] already bounds
+//! how long a silent connection is allowed to live, but NAT/firewall
+//! bindings can drop an otherwise-healthy idle connection well before
+//! that. [`KeepAliveTimer`] tracks time since the last successful read and,
+//! once `keepalive_interval_secs` elapses with no traffic, asks the caller
+//! to send a lightweight CoT ping; if `client_timeout_secs` then elapses
+//! with still no response, it asks the caller to close the connection.
+//!
+//! The timer itself does no I/O — callers drive it alongside their read
+//! future with `tokio::select!`, which keeps the same driver usable from
+//! the TCP, TLS, and QUIC transports.
+
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// What a connection handler should do after [`KeepAliveTimer::tick`] fires
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeepAliveAction {
+    /// No traffic for `keepalive_interval` and no ping pending; send one and keep waiting
+    SendPing,
+    /// `client_timeout_secs` elapsed with no response to a ping; disconnect
+    Disconnect,
+}
+
+/// Per-connection keep-alive driver
+///
+/// Reset on every successful read; ticked (e.g. via `tokio::select!`)
+/// against the connection's read future.
+pub struct KeepAliveTimer {
+    keepalive_interval: Duration,
+    client_timeout: Duration,
+    last_activity: Instant,
+    last_ping: Option<Instant>,
+}
+
+impl KeepAliveTimer {
+    /// Build a timer from the server's configured intervals
+    ///
+    /// `keepalive_interval` is how long to wait for traffic before
+    /// sending a ping; `client_timeout` is how long to wait for a
+    /// response to that ping before giving up on the connection.
+    pub fn new(keepalive_interval: Duration, client_timeout: Duration) -> Self {
+        Self {
+            keepalive_interval,
+            client_timeout,
+            last_activity: Instant::now(),
+            last_ping: None,
+        }
+    }
+
+    /// Record a successful read, clearing any pending ping
+    pub fn reset(&mut self) {
+        self.last_activity = Instant::now();
+        self.last_ping = None;
+    }
+
+    /// The instant this timer should next be polled
+    ///
+    /// Pass to `tokio::time::sleep_until` inside a `tokio::select!` next to
+    /// the connection's read future.
+    pub fn deadline(&self) -> Instant {
+        match self.last_ping {
+            Some(ping_sent) => ping_sent + self.client_timeout,
+            None => self.last_activity + self.keepalive_interval,
+        }
+    }
+
+    /// Called when [`Self::deadline`] elapses with no intervening [`Self::reset`]
+    ///
+    /// Returns [`KeepAliveAction::SendPing`] the first time a connection
+    /// goes quiet for `keepalive_interval`, and [`KeepAliveAction::Disconnect`]
+    /// if `client_timeout` then elapses with still no response.
+    pub fn tick(&mut self) -> KeepAliveAction {
+        if self.last_ping.is_some() {
+            KeepAliveAction::Disconnect
+        } else {
+            self.last_ping = Some(Instant::now());
+            KeepAliveAction::SendPing
+        }
+    }
+}
+
+/// The CoT "ping" event sent on an idle connection to prove liveness
+///
+/// A minimal, stale-immediately CoT event rather than a real position
+/// report; clients should not route or display it, only use its receipt
+/// (or, for protocols that support it, their own reply) as a liveness
+/// signal.
+pub fn cot_ping_payload(uid: &str) -> Vec<u8> {
+    let now = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?><event version="2.0" uid="{uid}" type="t-x-c-t" time="{now}" start="{now}" stale="{now}" how="m-g"><point lat="0.0" lon="0.0" hae="0.0" ce="9999999.0" le="9999999.0"/></event>"#,
+        uid = uid,
+        now = now,
+    )
+    .into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn stays_quiet_before_the_keepalive_interval_elapses() {
+        let timer = KeepAliveTimer::new(Duration::from_secs(15), Duration::from_secs(60));
+        assert!(timer.deadline() > Instant::now());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn sends_one_ping_then_disconnects_if_still_silent() {
+        let mut timer = KeepAliveTimer::new(Duration::from_secs(15), Duration::from_secs(60));
+
+        tokio::time::advance(Duration::from_secs(15)).await;
+        assert_eq!(timer.tick(), KeepAliveAction::SendPing);
+
+        // With a ping pending, the next deadline is `client_timeout` after
+        // the ping rather than another `keepalive_interval`.
+        let deadline_after_ping = timer.deadline();
+        assert_eq!(deadline_after_ping, Instant::now() + Duration::from_secs(60));
+
+        tokio::time::advance(Duration::from_secs(60)).await;
+        assert_eq!(timer.tick(), KeepAliveAction::Disconnect);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn reset_clears_a_pending_ping_and_restarts_the_interval() {
+        let mut timer = KeepAliveTimer::new(Duration::from_secs(15), Duration::from_secs(60));
+
+        tokio::time::advance(Duration::from_secs(15)).await;
+        assert_eq!(timer.tick(), KeepAliveAction::SendPing);
+
+        timer.reset();
+        assert_eq!(timer.deadline(), Instant::now() + Duration::from_secs(15));
+
+        // Going quiet again after a reset pings again rather than disconnecting.
+        tokio::time::advance(Duration::from_secs(15)).await;
+        assert_eq!(timer.tick(), KeepAliveAction::SendPing);
+    }
+
+    #[test]
+    fn cot_ping_payload_is_well_formed_xml_with_the_given_uid() {
+        let payload = cot_ping_payload("omnitak-test-uid");
+        let xml = String::from_utf8(payload).unwrap();
+        assert!(xml.contains(r#"uid="omnitak-test-uid""#));
+        assert!(xml.contains(r#"type="t-x-c-t""#));
+    }
+}
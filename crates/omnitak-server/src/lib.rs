@@ -0,0 +1,12 @@
+//! OmniTAK server: routes CoT over TCP, TLS, and QUIC
+
+pub mod config;
+pub mod error;
+pub mod keepalive;
+pub mod quic;
+pub mod server;
+pub mod tls;
+
+pub use config::ServerConfig;
+pub use error::{Result, ServerError};
+pub use server::{ServerStats, TakServer};
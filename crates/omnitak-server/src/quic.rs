@@ -0,0 +1,271 @@
+//! QUIC transport for TAK server
+//!
+//! A `quinn`-based alternative to the TCP and TLS-over-TCP transports in
+//! [`crate::tls`]: each accepted connection gets its own task, bidirectional
+//! streams carry CoT XML like the TCP path, and unreliable datagrams are
+//! accepted for position updates that can be dropped under loss instead of
+//! retransmitted. Stream reads and datagrams are handed on as raw bytes;
+//! they are not split into individual `<event>` documents.
+
+use crate::config::KeepAliveConfig;
+use crate::error::{Result, ServerError};
+use crate::keepalive::{cot_ping_payload, KeepAliveAction, KeepAliveTimer};
+use crate::tls::build_rustls_server_config;
+use quinn::{Connection, Endpoint, RecvStream, SendStream, TransportConfig};
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::{Mutex, Notify};
+use tracing::{debug, info, warn};
+
+/// A handle for CoT XML received over a QUIC stream or datagram
+pub type CotBytes = Vec<u8>;
+
+/// QUIC listener accepting CoT traffic alongside the TCP/TLS transports
+pub struct QuicListener {
+    endpoint: Endpoint,
+    local_addr: SocketAddr,
+    keepalive_cfg: KeepAliveConfig,
+    client_timeout: Duration,
+}
+
+impl QuicListener {
+    /// Bind a QUIC endpoint reusing the server's TLS identity
+    ///
+    /// `client_timeout_secs` drives both the QUIC transport's idle timeout
+    /// (keep-alives are sent at half that interval so NAT/firewall bindings
+    /// stay open) and the app-level [`KeepAliveTimer`] deadline used by
+    /// [`handle_connection`], mirroring the TCP/TLS accept loops in
+    /// [`crate::server`].
+    pub fn bind(
+        addr: SocketAddr,
+        cert_path: &Path,
+        key_path: &Path,
+        ca_path: Option<&Path>,
+        require_client_cert: bool,
+        key_password: Option<&str>,
+        client_timeout_secs: u64,
+        keepalive_cfg: KeepAliveConfig,
+    ) -> Result<Self> {
+        let rustls_config = build_rustls_server_config(
+            cert_path,
+            key_path,
+            ca_path,
+            require_client_cert,
+            key_password,
+        )?;
+
+        let quinn_crypto = quinn::crypto::rustls::QuicServerConfig::try_from(
+            rustls_config.as_ref().clone(),
+        )
+        .map_err(|e| ServerError::Tls(format!("Failed to build QUIC crypto config: {}", e)))?;
+
+        let mut server_config = quinn::ServerConfig::with_crypto(Arc::new(quinn_crypto));
+
+        let mut transport = TransportConfig::default();
+        let idle_timeout = Duration::from_secs(client_timeout_secs)
+            .try_into()
+            .map_err(|e| ServerError::Tls(format!("Invalid QUIC idle timeout: {:?}", e)))?;
+        transport.max_idle_timeout(Some(idle_timeout));
+        transport.keep_alive_interval(Some(Duration::from_secs(client_timeout_secs.max(2) / 2)));
+        server_config.transport_config(Arc::new(transport));
+
+        let endpoint = Endpoint::server(server_config, addr)
+            .map_err(|e| ServerError::Tls(format!("Failed to bind QUIC endpoint: {}", e)))?;
+        let local_addr = endpoint
+            .local_addr()
+            .map_err(|e| ServerError::Tls(format!("Failed to read QUIC local addr: {}", e)))?;
+
+        Ok(Self {
+            endpoint,
+            local_addr,
+            keepalive_cfg,
+            client_timeout: Duration::from_secs(client_timeout_secs),
+        })
+    }
+
+    /// The address this listener is bound to
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Accept connections until the endpoint is closed, spawning a task per connection
+    ///
+    /// `on_cot` is invoked with the raw bytes of each stream read and each
+    /// datagram; it is cloned into every connection task.
+    pub async fn serve<F>(self, on_cot: F)
+    where
+        F: Fn(CotBytes) + Clone + Send + Sync + 'static,
+    {
+        info!("QUIC listener serving on {}", self.local_addr);
+        while let Some(incoming) = self.endpoint.accept().await {
+            let on_cot = on_cot.clone();
+            let keepalive_cfg = self.keepalive_cfg.clone();
+            let client_timeout = self.client_timeout;
+            tokio::spawn(async move {
+                match incoming.await {
+                    Ok(connection) => {
+                        handle_connection(connection, on_cot, keepalive_cfg, client_timeout).await
+                    }
+                    Err(e) => warn!("QUIC handshake failed: {}", e),
+                }
+            });
+        }
+    }
+
+    /// Close the endpoint, rejecting new connections and in-progress handshakes
+    pub fn close(&self) {
+        self.endpoint.close(0u32.into(), b"server shutting down");
+    }
+}
+
+/// A [`KeepAliveTimer`] shared across every stream task of one QUIC
+/// connection, plus a [`Notify`] so a reset on any stream wakes the
+/// connection-level loop rather than leaving it asleep on a deadline it
+/// captured before the reset happened.
+struct SharedKeepAlive {
+    timer: Mutex<KeepAliveTimer>,
+    notify: Notify,
+}
+
+impl SharedKeepAlive {
+    fn new(keepalive_interval: Duration, client_timeout: Duration) -> Self {
+        Self {
+            timer: Mutex::new(KeepAliveTimer::new(keepalive_interval, client_timeout)),
+            notify: Notify::new(),
+        }
+    }
+
+    async fn reset(&self) {
+        self.timer.lock().await.reset();
+        self.notify.notify_one();
+    }
+}
+
+/// Drive a single QUIC connection: accept streams/datagrams and tear the
+/// whole connection down (not just one stream) if it goes idle
+///
+/// The keep-alive timer is shared across every stream task spawned for
+/// this connection via an `Arc<SharedKeepAlive>` so a read on any stream,
+/// or a datagram, resets it and wakes this loop — matching the
+/// "per-connection keep-alive driver" the TCP/TLS transports use in
+/// [`crate::server::drive_connection`].
+async fn handle_connection<F>(
+    connection: Connection,
+    on_cot: F,
+    keepalive_cfg: KeepAliveConfig,
+    client_timeout: Duration,
+) where
+    F: Fn(CotBytes) + Clone + Send + Sync + 'static,
+{
+    let remote = connection.remote_address();
+    debug!("QUIC connection established from {}", remote);
+
+    let keepalive = Arc::new(SharedKeepAlive::new(
+        Duration::from_secs(keepalive_cfg.keepalive_interval_secs.max(1)),
+        client_timeout,
+    ));
+
+    loop {
+        let deadline = keepalive.timer.lock().await.deadline();
+        tokio::select! {
+            stream = connection.accept_bi() => {
+                match stream {
+                    Ok((send, recv)) => {
+                        let on_cot = on_cot.clone();
+                        let keepalive = Arc::clone(&keepalive);
+                        tokio::spawn(async move {
+                            if let Err(e) = handle_bi_stream(send, recv, on_cot, keepalive).await {
+                                debug!("QUIC stream from {} ended: {}", remote, e);
+                            }
+                        });
+                    }
+                    Err(e) => {
+                        debug!("QUIC connection from {} closed: {}", remote, e);
+                        break;
+                    }
+                }
+            }
+            datagram = connection.read_datagram() => {
+                match datagram {
+                    Ok(bytes) => {
+                        keepalive.reset().await;
+                        on_cot(bytes.to_vec());
+                    }
+                    Err(e) => {
+                        debug!("QUIC connection from {} closed: {}", remote, e);
+                        break;
+                    }
+                }
+            }
+            _ = keepalive.notify.notified() => {
+                // A sibling stream/datagram reset the timer; loop back to
+                // recompute `deadline` instead of pinging on stale state.
+            }
+            _ = tokio::time::sleep_until(deadline), if keepalive_cfg.enabled => {
+                match keepalive.timer.lock().await.tick() {
+                    KeepAliveAction::SendPing => {
+                        let payload = keepalive_cfg
+                            .ping_payload
+                            .clone()
+                            .unwrap_or_else(|| cot_ping_payload("omnitak-quic-keepalive"));
+                        match connection.open_uni().await {
+                            Ok(mut ping_stream) => {
+                                if let Err(e) = ping_stream.write_all(&payload).await {
+                                    debug!("QUIC keep-alive ping to {} failed: {}", remote, e);
+                                }
+                                let _ = ping_stream.finish().await;
+                            }
+                            Err(e) => debug!("QUIC keep-alive ping to {} failed: {}", remote, e),
+                        }
+                    }
+                    KeepAliveAction::Disconnect => {
+                        debug!("QUIC connection from {} idle past client timeout, closing", remote);
+                        connection.close(0u32.into(), b"idle timeout");
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Read CoT XML off a bidirectional stream
+///
+/// Like the TCP transport, each read's bytes go to `on_cot` as-is; a read
+/// is not split into, or joined up to, whole `<event>...</event>`
+/// documents. Every successful read resets the connection-level
+/// `keepalive` timer shared with [`handle_connection`] and any sibling
+/// streams.
+async fn handle_bi_stream<F>(
+    mut send: SendStream,
+    mut recv: RecvStream,
+    on_cot: F,
+    keepalive: Arc<SharedKeepAlive>,
+) -> Result<()>
+where
+    F: Fn(CotBytes) + Clone + Send + Sync + 'static,
+{
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = match recv.read(&mut buf).await {
+            Ok(Some(n)) => n,
+            Ok(None) => break,
+            Err(e) => return Err(ServerError::Tls(format!("QUIC stream read error: {}", e))),
+        };
+        if n == 0 {
+            continue;
+        }
+        keepalive.reset().await;
+        on_cot(buf[..n].to_vec());
+    }
+
+    // Streams are half-closed from the client side once it has sent its
+    // CoT payload; flush and let `send` drop to finish our half.
+    send.flush()
+        .await
+        .map_err(|e| ServerError::Tls(format!("QUIC stream flush error: {}", e)))?;
+    Ok(())
+}
@@ -0,0 +1,290 @@
+//! TAK server: accepts CoT connections over TCP, TLS, and QUIC
+//!
+//! Each transport runs its own accept loop as a background task; every
+//! accepted TCP or TLS connection is driven by [`drive_connection`], which
+//! reads CoT XML while a [`KeepAliveTimer`] pings (and eventually
+//! disconnects) an otherwise-idle peer. QUIC connections are driven by
+//! [`crate::quic`].
+
+use crate::config::{KeepAliveConfig, ServerConfig};
+use crate::error::{Result, ServerError};
+use crate::keepalive::{cot_ping_payload, KeepAliveAction, KeepAliveTimer};
+use crate::quic::QuicListener;
+use crate::tls::{register_reloadable_acceptor, ReloadableTlsAcceptor};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// Snapshot of server activity, returned by [`TakServer::stats`]
+#[derive(Debug, Clone, Default)]
+pub struct ServerStats {
+    pub total_messages: u64,
+    pub client_count: usize,
+}
+
+#[derive(Default)]
+struct Counters {
+    messages: AtomicU64,
+    clients: AtomicUsize,
+}
+
+/// Runs the TCP, TLS, and QUIC transports for a single TAK server instance
+pub struct TakServer {
+    config: ServerConfig,
+    counters: Arc<Counters>,
+    tasks: Vec<JoinHandle<()>>,
+    tls_acceptor: Option<Arc<ReloadableTlsAcceptor>>,
+    quic_listener: Option<QuicListener>,
+}
+
+impl TakServer {
+    /// Build a server from its configuration, loading the TLS identity and
+    /// binding the QUIC endpoint (if configured)
+    pub fn new(config: ServerConfig) -> Result<Self> {
+        let tls_acceptor = match &config.tls {
+            Some(tls) => {
+                let acceptor = ReloadableTlsAcceptor::new(
+                    &tls.cert_path,
+                    &tls.key_path,
+                    tls.ca_path.as_deref(),
+                    tls.require_client_cert,
+                    tls.key_password.as_deref(),
+                )?;
+                // So `omnitak_tls_reload()` (and any other future embedder) can
+                // trigger a reload of the identity this server is actually using.
+                register_reloadable_acceptor(Arc::clone(&acceptor));
+                Some(acceptor)
+            }
+            None => None,
+        };
+
+        let quic_listener = if config.quic_port != 0 {
+            let tls = config.tls.as_ref().ok_or_else(|| {
+                ServerError::Config("quic_port is set but no tls identity is configured".into())
+            })?;
+            Some(QuicListener::bind(
+                SocketAddr::new(config.bind_address, config.quic_port),
+                &tls.cert_path,
+                &tls.key_path,
+                tls.ca_path.as_deref(),
+                tls.require_client_cert,
+                tls.key_password.as_deref(),
+                config.client_timeout_secs,
+                config.keepalive.clone(),
+            )?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            config,
+            counters: Arc::new(Counters::default()),
+            tasks: Vec::new(),
+            tls_acceptor,
+            quic_listener,
+        })
+    }
+
+    /// Bind and spawn the accept loop for every transport enabled in the config
+    pub async fn start(&mut self) -> Result<()> {
+        let client_timeout = Duration::from_secs(self.config.client_timeout_secs);
+
+        if self.config.tcp_port != 0 {
+            let addr = SocketAddr::new(self.config.bind_address, self.config.tcp_port);
+            let listener = TcpListener::bind(addr).await?;
+            info!("TCP listener bound on {}", addr);
+            self.tasks.push(spawn_tcp_accept_loop(
+                listener,
+                Arc::clone(&self.counters),
+                self.config.keepalive.clone(),
+                client_timeout,
+            ));
+        }
+
+        if self.config.tls_port != 0 {
+            let acceptor = self.tls_acceptor.clone().ok_or_else(|| {
+                ServerError::Config("tls_port is set but no tls identity is configured".into())
+            })?;
+            let addr = SocketAddr::new(self.config.bind_address, self.config.tls_port);
+            let listener = TcpListener::bind(addr).await?;
+            info!("TLS listener bound on {}", addr);
+            self.tasks.push(spawn_tls_accept_loop(
+                listener,
+                acceptor,
+                Arc::clone(&self.counters),
+                self.config.keepalive.clone(),
+                client_timeout,
+            ));
+        }
+
+        if let Some(quic) = self.quic_listener.take() {
+            info!("QUIC listener bound on {}", quic.local_addr());
+            let counters = Arc::clone(&self.counters);
+            self.tasks.push(tokio::spawn(async move {
+                quic.serve(move |_cot| {
+                    counters.messages.fetch_add(1, Ordering::Relaxed);
+                })
+                .await;
+            }));
+        }
+
+        Ok(())
+    }
+
+    /// Abort every accept-loop task started by [`Self::start`]
+    pub async fn stop(&mut self) -> Result<()> {
+        for task in self.tasks.drain(..) {
+            task.abort();
+        }
+        Ok(())
+    }
+
+    /// A snapshot of messages routed and clients currently connected
+    ///
+    /// `client_count` covers TCP and TLS connections only; QUIC connections
+    /// are not counted.
+    pub fn stats(&self) -> ServerStats {
+        ServerStats {
+            total_messages: self.counters.messages.load(Ordering::Relaxed),
+            client_count: self.counters.clients.load(Ordering::Relaxed),
+        }
+    }
+}
+
+fn spawn_tcp_accept_loop(
+    listener: TcpListener,
+    counters: Arc<Counters>,
+    keepalive_cfg: KeepAliveConfig,
+    client_timeout: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    warn!("TCP accept error: {}", e);
+                    break;
+                }
+            };
+            let counters = Arc::clone(&counters);
+            let keepalive_cfg = keepalive_cfg.clone();
+            tokio::spawn(async move {
+                counters.clients.fetch_add(1, Ordering::Relaxed);
+                if let Err(e) =
+                    drive_connection(stream, &counters, &keepalive_cfg, client_timeout).await
+                {
+                    debug!("TCP connection from {} ended: {}", peer, e);
+                }
+                counters.clients.fetch_sub(1, Ordering::Relaxed);
+            });
+        }
+    })
+}
+
+fn spawn_tls_accept_loop(
+    listener: TcpListener,
+    acceptor: Arc<ReloadableTlsAcceptor>,
+    counters: Arc<Counters>,
+    keepalive_cfg: KeepAliveConfig,
+    client_timeout: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    warn!("TLS accept error: {}", e);
+                    break;
+                }
+            };
+            let acceptor = Arc::clone(&acceptor);
+            let counters = Arc::clone(&counters);
+            let keepalive_cfg = keepalive_cfg.clone();
+            tokio::spawn(async move {
+                let tls_stream = match acceptor.accept(stream).await {
+                    Ok(s) => s,
+                    Err(e) => {
+                        warn!("TLS handshake with {} failed: {}", peer, e);
+                        return;
+                    }
+                };
+                counters.clients.fetch_add(1, Ordering::Relaxed);
+                if let Err(e) =
+                    drive_connection(tls_stream, &counters, &keepalive_cfg, client_timeout).await
+                {
+                    debug!("TLS connection from {} ended: {}", peer, e);
+                }
+                counters.clients.fetch_sub(1, Ordering::Relaxed);
+            });
+        }
+    })
+}
+
+/// Read CoT off a connection, driving a keep-alive timer alongside it
+///
+/// Each successful read counts as one message; reads are not split into
+/// individual `<event>` documents.
+///
+/// Shared by the TCP and TLS accept loops (and mirrored by the QUIC
+/// transport in [`crate::quic`]) so all three transports tear down an idle
+/// connection the same way: ping once `keepalive_interval_secs` elapses
+/// with no traffic, then disconnect if `client_timeout_secs` elapses with
+/// no response to that ping.
+async fn drive_connection<S>(
+    mut stream: S,
+    counters: &Counters,
+    keepalive_cfg: &KeepAliveConfig,
+    client_timeout: Duration,
+) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut timer = KeepAliveTimer::new(
+        Duration::from_secs(keepalive_cfg.keepalive_interval_secs.max(1)),
+        client_timeout,
+    );
+    let mut buf = vec![0u8; 64 * 1024];
+
+    loop {
+        if !keepalive_cfg.enabled {
+            let n = stream.read(&mut buf).await?;
+            if n == 0 {
+                return Ok(());
+            }
+            counters.messages.fetch_add(1, Ordering::Relaxed);
+            continue;
+        }
+
+        tokio::select! {
+            read = stream.read(&mut buf) => {
+                let n = read?;
+                if n == 0 {
+                    return Ok(());
+                }
+                timer.reset();
+                counters.messages.fetch_add(1, Ordering::Relaxed);
+            }
+            _ = tokio::time::sleep_until(timer.deadline()) => {
+                match timer.tick() {
+                    KeepAliveAction::SendPing => {
+                        let payload = keepalive_cfg
+                            .ping_payload
+                            .clone()
+                            .unwrap_or_else(|| cot_ping_payload("omnitak-keepalive"));
+                        stream.write_all(&payload).await?;
+                    }
+                    KeepAliveAction::Disconnect => {
+                        return Err(ServerError::Connection(
+                            "idle timeout: no traffic after keep-alive ping".into(),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,14 +1,19 @@
 //! TLS support for TAK server
 
 use crate::error::{Result, ServerError};
+use arc_swap::ArcSwap;
+use openssl::pkcs12::Pkcs12;
+use pkcs8::{der::Decode, EncryptedPrivateKeyInfo};
 use rustls::server::AllowAnyAuthenticatedClient;
 use rustls::{Certificate, PrivateKey, RootCertStore, ServerConfig as RustlsConfig};
-use rustls_pemfile::{certs, pkcs8_private_keys, rsa_private_keys};
+use rustls_pemfile::certs;
 use std::fs::File;
 use std::io::BufReader;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tokio_rustls::TlsAcceptor;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_rustls::{Accept, TlsAcceptor};
+use tracing::{info, warn};
 
 /// Load TLS server configuration from PEM files
 pub fn load_tls_config(
@@ -17,6 +22,43 @@ pub fn load_tls_config(
     ca_path: Option<&Path>,
     require_client_cert: bool,
 ) -> Result<TlsAcceptor> {
+    load_tls_config_with_key_password(cert_path, key_path, ca_path, require_client_cert, None)
+}
+
+/// Load TLS server configuration from PEM files, decrypting the key with `key_password` if set
+///
+/// `key_password` corresponds to `TlsConfig::key_password` and is only
+/// needed when `key_path` holds an `ENCRYPTED PRIVATE KEY` PEM block.
+pub fn load_tls_config_with_key_password(
+    cert_path: &Path,
+    key_path: &Path,
+    ca_path: Option<&Path>,
+    require_client_cert: bool,
+    key_password: Option<&str>,
+) -> Result<TlsAcceptor> {
+    let config = build_rustls_server_config(
+        cert_path,
+        key_path,
+        ca_path,
+        require_client_cert,
+        key_password,
+    )?;
+    Ok(TlsAcceptor::from(config))
+}
+
+/// Build a `rustls::ServerConfig` from PEM files
+///
+/// This is the shared core of [`load_tls_config`]: it loads the same cert
+/// chain, private key, and client-cert verifier, but returns the raw
+/// `rustls` config instead of a `tokio_rustls::TlsAcceptor` so other
+/// transports (e.g. QUIC via `quinn`) can build on top of it.
+pub fn build_rustls_server_config(
+    cert_path: &Path,
+    key_path: &Path,
+    ca_path: Option<&Path>,
+    require_client_cert: bool,
+    key_password: Option<&str>,
+) -> Result<Arc<RustlsConfig>> {
     // Load server certificate
     let cert_file = File::open(cert_path).map_err(|e| {
         ServerError::Certificate(format!("Failed to open cert file {}: {}", cert_path.display(), e))
@@ -33,30 +75,13 @@ pub fn load_tls_config(
         return Err(ServerError::Certificate("No certificates found in cert file".into()));
     }
 
-    // Load private key
-    let key_file = File::open(key_path).map_err(|e| {
+    // Load private key: accept PKCS#8, SEC1/EC, RSA, and password-encrypted
+    // PKCS#8 blocks, so ECDSA server keys (common for modern TAK/mTLS
+    // setups) and encrypted keys both work, not just plain RSA/PKCS8.
+    let key_bytes = std::fs::read(key_path).map_err(|e| {
         ServerError::Certificate(format!("Failed to open key file {}: {}", key_path.display(), e))
     })?;
-    let mut key_reader = BufReader::new(key_file);
-
-    // Try PKCS8 first, then RSA
-    let keys = pkcs8_private_keys(&mut key_reader)
-        .map_err(|e| ServerError::Certificate(format!("Failed to parse PKCS8 key: {}", e)))?;
-
-    let key = if !keys.is_empty() {
-        PrivateKey(keys[0].clone())
-    } else {
-        // Try RSA format
-        let key_file = File::open(key_path)?;
-        let mut key_reader = BufReader::new(key_file);
-        let keys = rsa_private_keys(&mut key_reader)
-            .map_err(|e| ServerError::Certificate(format!("Failed to parse RSA key: {}", e)))?;
-
-        if keys.is_empty() {
-            return Err(ServerError::Certificate("No private keys found in key file".into()));
-        }
-        PrivateKey(keys[0].clone())
-    };
+    let key = parse_private_key_pem(&key_bytes, key_password)?;
 
     // Build TLS config
     let mut config = if let Some(ca_path) = ca_path {
@@ -113,5 +138,405 @@ pub fn load_tls_config(
     // Set ALPN protocols (optional, for future HTTP/2 support)
     config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
 
-    Ok(TlsAcceptor::from(Arc::new(config)))
+    Ok(Arc::new(config))
+}
+
+/// Parse a private key out of a PEM file, trying every format TAK servers show up with
+///
+/// Uses the first block in the file that is one of: PKCS#8 (`PRIVATE KEY`),
+/// SEC1/EC (`EC PRIVATE KEY`), RSA (`RSA PRIVATE KEY`), or password-encrypted
+/// PKCS#8 (`ENCRYPTED PRIVATE KEY`, decrypted with `key_password` via `pkcs8`).
+/// Returns the DER bytes as a `rustls::PrivateKey`, which `rustls::sign`
+/// accepts directly for all of the unencrypted formats above.
+fn parse_private_key_pem(pem_bytes: &[u8], key_password: Option<&str>) -> Result<PrivateKey> {
+    let blocks = pem::parse_many(pem_bytes)
+        .map_err(|e| ServerError::Certificate(format!("Failed to parse key PEM: {}", e)))?;
+
+    for block in &blocks {
+        match block.tag() {
+            "PRIVATE KEY" => {
+                return Ok(PrivateKey(block.contents().to_vec()));
+            }
+            "EC PRIVATE KEY" => {
+                return Ok(PrivateKey(block.contents().to_vec()));
+            }
+            "RSA PRIVATE KEY" => {
+                return Ok(PrivateKey(block.contents().to_vec()));
+            }
+            "ENCRYPTED PRIVATE KEY" => {
+                let password = key_password.ok_or_else(|| {
+                    ServerError::Certificate(
+                        "Key is password-encrypted but no key_password was provided".into(),
+                    )
+                })?;
+                let encrypted = EncryptedPrivateKeyInfo::from_der(block.contents())
+                    .map_err(|e| ServerError::Certificate(format!("Failed to parse encrypted PKCS#8 key: {}", e)))?;
+                let decrypted = encrypted.decrypt(password.as_bytes()).map_err(|e| {
+                    ServerError::Certificate(format!(
+                        "Failed to decrypt PKCS#8 key (wrong key_password?): {}",
+                        e
+                    ))
+                })?;
+                return Ok(PrivateKey(decrypted.as_bytes().to_vec()));
+            }
+            _ => continue,
+        }
+    }
+
+    Err(ServerError::Certificate(
+        "No private keys found in key file (tried PKCS#8, SEC1/EC, RSA, encrypted PKCS#8)".into(),
+    ))
+}
+
+/// Load a server identity (cert chain + private key) from a password-protected PKCS#12 bundle
+///
+/// An alternative to [`load_tls_config`]'s separate cert/key PEM files for
+/// callers that only have a `.p12`. Returns the same
+/// `(Vec<Certificate>, PrivateKey)` shape [`build_rustls_server_config`]
+/// consumes via `with_single_cert`.
+pub fn load_identity_p12(path: &Path, password: &str) -> Result<(Vec<Certificate>, PrivateKey)> {
+    let der = std::fs::read(path).map_err(|e| {
+        ServerError::Certificate(format!("Failed to open PKCS#12 file {}: {}", path.display(), e))
+    })?;
+
+    let pkcs12 = Pkcs12::from_der(&der)
+        .map_err(|e| ServerError::Certificate(format!("Failed to parse PKCS#12 file: {}", e)))?;
+    let parsed = pkcs12.parse2(password).map_err(|e| {
+        ServerError::Certificate(format!("Failed to unlock PKCS#12 identity (wrong password?): {}", e))
+    })?;
+
+    let leaf = parsed
+        .cert
+        .ok_or_else(|| ServerError::Certificate("PKCS#12 bundle has no leaf certificate".into()))?;
+    let leaf_der = leaf
+        .to_der()
+        .map_err(|e| ServerError::Certificate(format!("Failed to encode leaf certificate: {}", e)))?;
+
+    let mut chain = vec![Certificate(leaf_der)];
+    if let Some(ca_chain) = parsed.ca {
+        for ca_cert in ca_chain {
+            let der = ca_cert.to_der().map_err(|e| {
+                ServerError::Certificate(format!("Failed to encode CA certificate: {}", e))
+            })?;
+            chain.push(Certificate(der));
+        }
+    }
+
+    let pkey = parsed
+        .pkey
+        .ok_or_else(|| ServerError::Certificate("PKCS#12 bundle has no private key".into()))?;
+    let key_der = pkey
+        .private_key_to_der()
+        .map_err(|e| ServerError::Certificate(format!("Failed to encode private key: {}", e)))?;
+
+    Ok((chain, PrivateKey(key_der)))
+}
+
+/// A `TlsAcceptor` whose certificate/key material can be swapped without restarting the server
+///
+/// Wraps the inner `Arc<rustls::ServerConfig>` in an `ArcSwap` so
+/// [`ReloadableTlsAcceptor::reload`] can atomically publish a config
+/// rebuilt from disk (e.g. after the enrollment FFI renews a cert). Every
+/// in-flight connection keeps the config snapshot it started its
+/// handshake with; only new handshakes observe the swap.
+pub struct ReloadableTlsAcceptor {
+    config: ArcSwap<RustlsConfig>,
+    cert_path: PathBuf,
+    key_path: PathBuf,
+    ca_path: Option<PathBuf>,
+    require_client_cert: bool,
+    key_password: Option<String>,
+}
+
+impl ReloadableTlsAcceptor {
+    /// Build a reloadable acceptor from the same PEM files `load_tls_config` would use
+    pub fn new(
+        cert_path: &Path,
+        key_path: &Path,
+        ca_path: Option<&Path>,
+        require_client_cert: bool,
+        key_password: Option<&str>,
+    ) -> Result<Arc<Self>> {
+        let config = build_rustls_server_config(
+            cert_path,
+            key_path,
+            ca_path,
+            require_client_cert,
+            key_password,
+        )?;
+        Ok(Arc::new(Self {
+            config: ArcSwap::from(config),
+            cert_path: cert_path.to_path_buf(),
+            key_path: key_path.to_path_buf(),
+            ca_path: ca_path.map(Path::to_path_buf),
+            require_client_cert,
+            key_password: key_password.map(str::to_string),
+        }))
+    }
+
+    /// Re-read the cert/key/CA files from disk and atomically swap in the new config
+    ///
+    /// Connections already mid-handshake continue with the config they
+    /// loaded; only handshakes started after this call observe the new
+    /// material.
+    pub fn reload(&self) -> Result<()> {
+        let config = build_rustls_server_config(
+            &self.cert_path,
+            &self.key_path,
+            self.ca_path.as_deref(),
+            self.require_client_cert,
+            self.key_password.as_deref(),
+        )?;
+        self.config.store(config);
+        info!("TLS config reloaded from {}", self.cert_path.display());
+        Ok(())
+    }
+
+    /// Accept a TLS handshake using the currently published config
+    ///
+    /// Mirrors `tokio_rustls::TlsAcceptor::accept` so callers can swap a
+    /// plain `TlsAcceptor` for a `ReloadableTlsAcceptor` without changing
+    /// their accept loop.
+    pub fn accept<IO>(&self, stream: IO) -> Accept<IO>
+    where
+        IO: AsyncRead + AsyncWrite + Unpin,
+    {
+        TlsAcceptor::from(self.config.load_full()).accept(stream)
+    }
+
+    /// Watch `cert_path`/`key_path` for changes and reload automatically
+    ///
+    /// Cert rotation (certbot, ACME clients, k8s secret mounts, ...)
+    /// typically replaces a file by writing to a temp path and renaming it
+    /// over the original, which invalidates an inotify watch placed on the
+    /// original path/inode after the first rotation. So instead of watching
+    /// `cert_path`/`key_path` directly, this watches their parent
+    /// directories and filters events down to just those two filenames.
+    /// Spawns a background task driven by a `notify` filesystem watcher;
+    /// any write/create/rename event on either file triggers [`Self::reload`].
+    /// The returned `notify::RecommendedWatcher` must be kept alive for the
+    /// duration of the watch.
+    pub fn watch_for_changes(self: &Arc<Self>) -> Result<notify::RecommendedWatcher> {
+        use notify::{RecursiveMode, Watcher};
+
+        let watched_names: Vec<std::ffi::OsString> = [&self.cert_path, &self.key_path]
+            .iter()
+            .filter_map(|p| p.file_name().map(|n| n.to_os_string()))
+            .collect();
+
+        let acceptor = Arc::clone(self);
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            match res {
+                Ok(event) if event.kind.is_modify() || event.kind.is_create() => {
+                    let touches_watched_file = event
+                        .paths
+                        .iter()
+                        .filter_map(|p| p.file_name())
+                        .any(|name| watched_names.iter().any(|w| w == name));
+                    if touches_watched_file {
+                        if let Err(e) = acceptor.reload() {
+                            warn!("Failed to reload TLS config after filesystem event: {}", e);
+                        }
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => warn!("TLS certificate watcher error: {}", e),
+            }
+        })
+        .map_err(|e| ServerError::Tls(format!("Failed to start TLS certificate watcher: {}", e)))?;
+
+        // `Path::parent()` returns `Some("")` rather than `None` for a bare
+        // relative filename (e.g. `"server.crt"`), and watching `""`
+        // doesn't resolve against the cwd like I/O calls do, so fold that
+        // case into the same `.` fallback as `None`.
+        let parent_dir = |p: &Path| match p.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        let cert_dir = parent_dir(&self.cert_path);
+        let key_dir = parent_dir(&self.key_path);
+
+        watcher
+            .watch(cert_dir, RecursiveMode::NonRecursive)
+            .map_err(|e| ServerError::Tls(format!("Failed to watch {}: {}", cert_dir.display(), e)))?;
+        if key_dir != cert_dir {
+            watcher
+                .watch(key_dir, RecursiveMode::NonRecursive)
+                .map_err(|e| ServerError::Tls(format!("Failed to watch {}: {}", key_dir.display(), e)))?;
+        }
+
+        Ok(watcher)
+    }
+}
+
+static REGISTERED_TLS_ACCEPTOR: std::sync::OnceLock<std::sync::Mutex<Option<Arc<ReloadableTlsAcceptor>>>> =
+    std::sync::OnceLock::new();
+
+/// Register the acceptor a running [`crate::server::TakServer`] is actually using
+///
+/// Lets an embedder (e.g. the mobile FFI layer's `omnitak_tls_reload`)
+/// trigger a reload of the live server's TLS identity without the caller
+/// needing to hold its own reference to the `ReloadableTlsAcceptor`.
+pub fn register_reloadable_acceptor(acceptor: Arc<ReloadableTlsAcceptor>) {
+    let slot = REGISTERED_TLS_ACCEPTOR.get_or_init(|| std::sync::Mutex::new(None));
+    *slot.lock().unwrap() = Some(acceptor);
+}
+
+/// Whether a server has called [`register_reloadable_acceptor`] yet
+pub fn has_registered_tls_acceptor() -> bool {
+    REGISTERED_TLS_ACCEPTOR
+        .get()
+        .map(|slot| slot.lock().unwrap().is_some())
+        .unwrap_or(false)
+}
+
+/// Reload the registered acceptor's TLS identity from disk
+pub fn reload_registered_tls_acceptor() -> Result<()> {
+    let acceptor = REGISTERED_TLS_ACCEPTOR
+        .get()
+        .and_then(|slot| slot.lock().unwrap().clone())
+        .ok_or_else(|| ServerError::Tls("no TLS acceptor has been registered".into()))?;
+    acceptor.reload()
+}
+
+#[cfg(test)]
+mod p12_tests {
+    use super::*;
+    use openssl::pkcs12::Pkcs12;
+    use openssl::pkey::PKey;
+    use openssl::x509::X509;
+    use std::io::Write;
+
+    const P12_PASSWORD: &str = "test-password";
+
+    /// Generate a self-signed identity and write it out as a `.p12` bundle
+    fn write_test_p12() -> std::path::PathBuf {
+        let cert = rcgen::generate_simple_self_signed(vec!["omnitak-test".into()]).unwrap();
+        let cert_der = cert.serialize_der().unwrap();
+        let key_der = cert.serialize_private_key_der();
+
+        let x509 = X509::from_der(&cert_der).unwrap();
+        let pkey = PKey::private_key_from_der(&key_der).unwrap();
+
+        let pkcs12 = Pkcs12::builder()
+            .build2(P12_PASSWORD, "omnitak-test", &pkey, &x509)
+            .unwrap();
+
+        let path = std::env::temp_dir().join(format!("omnitak-test-{}.p12", std::process::id()));
+        let mut file = File::create(&path).unwrap();
+        file.write_all(&pkcs12.to_der().unwrap()).unwrap();
+        path
+    }
+
+    #[test]
+    fn load_identity_p12_round_trips_through_tls_handshake_and_reexport() {
+        let p12_path = write_test_p12();
+
+        // Load the identity back out of the bundle.
+        let (chain, key) = load_identity_p12(&p12_path, P12_PASSWORD).expect("load p12 identity");
+        assert_eq!(chain.len(), 1, "self-signed bundle has a single leaf cert");
+
+        // Start a TLS handshake using the loaded identity, entirely in-memory.
+        let server_config = RustlsConfig::builder()
+            .with_safe_defaults()
+            .with_no_client_auth()
+            .with_single_cert(chain.clone(), key)
+            .expect("build rustls server config from p12 identity");
+
+        let mut root_store = RootCertStore::empty();
+        root_store.add(&chain[0]).expect("trust self-signed leaf");
+        let client_config = rustls::ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(root_store)
+            .with_no_client_auth();
+
+        let server_name = rustls::ServerName::try_from("omnitak-test").unwrap();
+        let mut client_conn =
+            rustls::ClientConnection::new(Arc::new(client_config), server_name).unwrap();
+        let mut server_conn = rustls::ServerConnection::new(Arc::new(server_config)).unwrap();
+
+        // Shuttle handshake bytes between the two sides until both complete.
+        for _ in 0..10 {
+            if !client_conn.is_handshaking() && !server_conn.is_handshaking() {
+                break;
+            }
+            let mut buf = Vec::new();
+            if client_conn.wants_write() {
+                client_conn.write_tls(&mut buf).unwrap();
+                let mut cursor = std::io::Cursor::new(buf);
+                server_conn.read_tls(&mut cursor).unwrap();
+                server_conn.process_new_packets().unwrap();
+            }
+            let mut buf = Vec::new();
+            if server_conn.wants_write() {
+                server_conn.write_tls(&mut buf).unwrap();
+                let mut cursor = std::io::Cursor::new(buf);
+                client_conn.read_tls(&mut cursor).unwrap();
+                client_conn.process_new_packets().unwrap();
+            }
+        }
+        assert!(!client_conn.is_handshaking());
+        assert!(!server_conn.is_handshaking());
+
+        // Re-export the loaded identity back into a fresh PKCS#12 blob.
+        let x509 = X509::from_der(&chain[0].0).expect("decode leaf for re-export");
+        let key_der = load_identity_p12(&p12_path, P12_PASSWORD).unwrap().1;
+        let pkey = PKey::private_key_from_der(&key_der.0).expect("decode key for re-export");
+        let reexported = Pkcs12::builder()
+            .build2(P12_PASSWORD, "omnitak-test", &pkey, &x509)
+            .expect("re-export pkcs12")
+            .to_der()
+            .expect("serialize re-exported pkcs12");
+        assert!(!reexported.is_empty());
+
+        let _ = std::fs::remove_file(&p12_path);
+    }
+}
+
+#[cfg(test)]
+mod private_key_format_tests {
+    use super::*;
+    use openssl::ec::{EcGroup, EcKey};
+    use openssl::nid::Nid;
+    use openssl::pkey::PKey;
+    use openssl::rsa::Rsa;
+    use openssl::symm::Cipher;
+
+    #[test]
+    fn parses_pkcs8_key() {
+        let rsa = Rsa::generate(2048).unwrap();
+        let pkey = PKey::from_rsa(rsa).unwrap();
+        let pem = pkey.private_key_to_pem_pkcs8().unwrap();
+        parse_private_key_pem(&pem, None).expect("PKCS#8 key should parse");
+    }
+
+    #[test]
+    fn parses_sec1_ec_key() {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let ec_key = EcKey::generate(&group).unwrap();
+        let pem = ec_key.private_key_to_pem().unwrap();
+        parse_private_key_pem(&pem, None).expect("SEC1/EC key should parse");
+    }
+
+    #[test]
+    fn parses_rsa_pkcs1_key() {
+        let rsa = Rsa::generate(2048).unwrap();
+        let pem = rsa.private_key_to_pem().unwrap();
+        parse_private_key_pem(&pem, None).expect("RSA/PKCS#1 key should parse");
+    }
+
+    #[test]
+    fn parses_encrypted_pkcs8_key() {
+        let rsa = Rsa::generate(2048).unwrap();
+        let pkey = PKey::from_rsa(rsa).unwrap();
+        let password = "correct-horse-battery-staple";
+        let pem = pkey
+            .private_key_to_pem_pkcs8_passphrase(Cipher::aes_256_cbc(), password.as_bytes())
+            .unwrap();
+
+        parse_private_key_pem(&pem, None)
+            .err()
+            .expect("encrypted key without a password should fail");
+        parse_private_key_pem(&pem, Some(password)).expect("encrypted key with the right password should parse");
+    }
 }